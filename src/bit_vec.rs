@@ -4,12 +4,19 @@
 /// Bit vector object. Allows for setting, unsetting, flipping of
 /// bits.  Indexing beyond the bounds of the vector will raise an
 /// error.
+///
+/// Bits are packed into `u64` words rather than individual bytes,
+/// which is both more compact and lets `set`/`unset` maintain a
+/// running count of set bits cheaply (used by `BloomFilter` to
+/// estimate how full it is).
 
 pub struct BitVec {
-    // Just the bits, nothing but the bits...
-    bits: Vec<u8>,
+    // The bits, packed 64 to a word
+    words: Vec<u64>,
     // Size of the bit array for bounds checking
     pub size: usize,
+    // Running count of bits currently set to 1
+    num_bits_set: usize,
 }
 
 impl BitVec {
@@ -17,8 +24,31 @@ impl BitVec {
     /// Create a new `bitvec` with `size` bits
     pub fn new(size: usize) -> BitVec {
         BitVec {
-            bits: vec![0u8; size],
+            words: vec![0u64; size.div_ceil(64)],
             size: size,
+            num_bits_set: 0,
+        }
+    }
+
+    /// Reconstruct a `BitVec` from its raw byte array, as previously
+    /// returned by `get_bytes`, and the `size` (in bits) it was
+    /// created with. Used to load a bit vector that was serialized
+    /// elsewhere instead of building it up bit by bit.
+    #[allow(dead_code)]
+    pub fn from_parts(bytes: &[u8], size: usize) -> BitVec {
+        let words: Vec<u64> = bytes.chunks(8)
+                                    .map(|chunk| {
+                                        let mut word_bytes = [0u8; 8];
+                                        word_bytes[..chunk.len()].copy_from_slice(chunk);
+                                        u64::from_le_bytes(word_bytes)
+                                    })
+                                    .collect();
+        let num_bits_set = words.iter().map(|word| word.count_ones() as usize).sum();
+
+        BitVec {
+            words: words,
+            size: size,
+            num_bits_set: num_bits_set,
         }
     }
 
@@ -26,11 +56,7 @@ impl BitVec {
         if pos > self.size {
             panic!("Attempted to index beyond bounds of bit vector.");
         }
-        if (1 << (pos % 8)) & self.bits[pos / 8] > 0 {
-            true
-        } else {
-            false
-        }
+        (self.words[pos / 64] >> (pos % 64)) & 1 == 1
     }
 
     /// Set the bit at `pos` to 1
@@ -38,7 +64,11 @@ impl BitVec {
         if pos > self.size {
             panic!("Attempted to index beyond bounds of bit vector.");
         }
-        self.bits[pos / 8] |= 1 << (pos % 8);
+        let mask = 1u64 << (pos % 64);
+        if self.words[pos / 64] & mask == 0 {
+            self.num_bits_set += 1;
+        }
+        self.words[pos / 64] |= mask;
     }
 
     /// Set the bit at `pos` to 0
@@ -47,9 +77,11 @@ impl BitVec {
         if pos > self.size {
             panic!("Attempted to index beyond bounds of bit vector.");
         }
-        self.bits[pos / 8] &= {
-            0xFF ^ (1 << (pos % 8))
+        let mask = 1u64 << (pos % 64);
+        if self.words[pos / 64] & mask != 0 {
+            self.num_bits_set -= 1;
         }
+        self.words[pos / 64] &= !mask;
     }
 
     /// Flip the bit at `pos`. If the bit is 0 it becomes 1; if
@@ -59,13 +91,71 @@ impl BitVec {
         if pos > self.size {
             panic!("Attempted to index beyond bounds of bit vector.");
         }
-        self.bits[pos / 8] ^= 1 << (pos % 8);
+        let mask = 1u64 << (pos % 64);
+        if self.words[pos / 64] & mask == 0 {
+            self.num_bits_set += 1;
+        } else {
+            self.num_bits_set -= 1;
+        }
+        self.words[pos / 64] ^= mask;
     }
 
-    /// Return the raw bytes of the bit vector
+    /// The number of bits currently set to 1.
     #[allow(dead_code)]
-    pub fn get_bytes<'a>(&'a self) -> &'a [u8] {
-        &self.bits
+    pub fn num_bits_set(&self) -> usize {
+        self.num_bits_set
+    }
+
+    /// Return the raw bytes of the bit vector (each word in
+    /// little-endian order), as consumed by `from_parts`.
+    #[allow(dead_code)]
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.words.len() * 8);
+        for word in &self.words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Combine this bit vector with `other`, word-by-word, using
+    /// `combine` (e.g. bitwise OR for union, bitwise AND for
+    /// intersection). Panics if the two vectors don't have the same
+    /// size.
+    fn combine<F>(&self, other: &BitVec, combine: F) -> BitVec
+        where F: Fn(u64, u64) -> u64
+    {
+        if self.size != other.size {
+            panic!("Cannot combine bit vectors of different sizes.");
+        }
+
+        let words: Vec<u64> = self.words
+                                   .iter()
+                                   .zip(other.words.iter())
+                                   .map(|(&a, &b)| combine(a, b))
+                                   .collect();
+        let num_bits_set = words.iter().map(|word| word.count_ones() as usize).sum();
+
+        BitVec {
+            words: words,
+            size: self.size,
+            num_bits_set: num_bits_set,
+        }
+    }
+
+    /// Return a new `BitVec` with each bit set if it's set in either
+    /// `self` or `other`. Panics if the two vectors don't have the
+    /// same size.
+    #[allow(dead_code)]
+    pub fn union(&self, other: &BitVec) -> BitVec {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Return a new `BitVec` with each bit set only if it's set in
+    /// both `self` and `other`. Panics if the two vectors don't have
+    /// the same size.
+    #[allow(dead_code)]
+    pub fn intersect(&self, other: &BitVec) -> BitVec {
+        self.combine(other, |a, b| a & b)
     }
 
 }
@@ -73,14 +163,14 @@ impl BitVec {
 #[test]
 fn bit_vec_create_test() {
     let tester: BitVec = BitVec::new(8);
-    assert!(tester.bits[0] == 0);
+    assert!(tester.words[0] == 0);
 }
 
 #[test]
 fn bit_vec_set_test() {
     let mut tester: BitVec = BitVec::new(8);
     tester.set(5);
-    assert!(tester.bits[0] == 32);
+    assert!(tester.words[0] == 32);
     let res = tester.is_set(5);
     assert!(res == true);
 }
@@ -100,7 +190,7 @@ fn bit_vec_unset_test() {
     assert!(tester.is_set(5) == true);
     tester.unset(5);
     assert!(tester.is_set(5) == false);
-    assert!(tester.bits[0] == 0);
+    assert!(tester.words[0] == 0);
 }
 
 #[test]
@@ -118,3 +208,58 @@ fn bit_vec_out_of_bounds_test() {
     let mut tester: BitVec = BitVec::new(8);
     tester.set(15);
 }
+
+#[test]
+fn bit_vec_union_test() {
+    let mut a: BitVec = BitVec::new(8);
+    let mut b: BitVec = BitVec::new(8);
+    a.set(5);
+    b.set(6);
+    let unioned = a.union(&b);
+    assert!(unioned.is_set(5) == true);
+    assert!(unioned.is_set(6) == true);
+    assert!(unioned.is_set(0) == false);
+}
+
+#[test]
+fn bit_vec_intersect_test() {
+    let mut a: BitVec = BitVec::new(8);
+    let mut b: BitVec = BitVec::new(8);
+    a.set(5);
+    a.set(6);
+    b.set(6);
+    let intersected = a.intersect(&b);
+    assert!(intersected.is_set(5) == false);
+    assert!(intersected.is_set(6) == true);
+}
+
+#[test]
+#[should_panic]
+fn bit_vec_combine_size_mismatch_test() {
+    let a: BitVec = BitVec::new(8);
+    let b: BitVec = BitVec::new(16);
+    a.union(&b);
+}
+
+#[test]
+fn bit_vec_from_parts_test() {
+    let mut original: BitVec = BitVec::new(8);
+    original.set(5);
+    let rebuilt: BitVec = BitVec::from_parts(&original.get_bytes(), original.size);
+    assert!(rebuilt.is_set(5) == true);
+    assert!(rebuilt.is_set(6) == false);
+}
+
+#[test]
+fn bit_vec_num_bits_set_test() {
+    let mut tester: BitVec = BitVec::new(8);
+    assert!(tester.num_bits_set() == 0);
+    tester.set(5);
+    tester.set(6);
+    assert!(tester.num_bits_set() == 2);
+    // Setting an already-set bit doesn't double count
+    tester.set(5);
+    assert!(tester.num_bits_set() == 2);
+    tester.unset(5);
+    assert!(tester.num_bits_set() == 1);
+}
@@ -1,7 +1,3 @@
-
-#![feature(step_by)]
-
-
 //! A simple bloom filter implementation.
 //! A bloom filter is a compact probabilistic data structure that
 //! affords storage savings in favor of a chance of false positives
@@ -14,7 +10,99 @@ mod bit_vec;
 
 
 use bit_vec::BitVec;
-use murmur3::murmur3_32_seeded;
+use murmur3::{murmur3_32_seeded, murmur3_32_seeded_bytes};
+
+/// Types that can be hashed into a `BloomFilter`'s bit array.
+/// Implementing this for a type lets it be used as a key with
+/// `insert`/`maybe_present` without having to stringify it first.
+pub trait BloomHashIndex {
+    /// Hash `self` using `seed` as the hash function's seed. A
+    /// `BloomFilter` calls this with seeds 0 and 1 for the two base
+    /// hashes its `k` bit indices are derived from, and with further
+    /// seeds only when an index has to be redrawn (see
+    /// `uniform_index`).
+    fn hash_at_index(&self, seed: u32) -> u32;
+}
+
+impl BloomHashIndex for str {
+    fn hash_at_index(&self, seed: u32) -> u32 {
+        murmur3_32_seeded(self, seed)
+    }
+}
+
+impl BloomHashIndex for [u8] {
+    fn hash_at_index(&self, seed: u32) -> u32 {
+        murmur3_32_seeded_bytes(self, seed)
+    }
+}
+
+impl<'a, T: BloomHashIndex + ?Sized> BloomHashIndex for &'a T {
+    fn hash_at_index(&self, seed: u32) -> u32 {
+        (**self).hash_at_index(seed)
+    }
+}
+
+macro_rules! impl_bloom_hash_index_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl BloomHashIndex for $t {
+                fn hash_at_index(&self, seed: u32) -> u32 {
+                    murmur3_32_seeded_bytes(&self.to_le_bytes(), seed)
+                }
+            }
+        )*
+    }
+}
+
+impl_bloom_hash_index_for_int!(i8, u8, i16, u16, i32, u32, i64, u64, isize, usize);
+
+/// Derive the `k` bit/counter indices for `value` against an array
+/// of `size` slots using `num_hashes` hash functions, via double
+/// hashing (Kirsch-Mitzenmacher): rather than running the full hash
+/// `k` times, compute two base hashes `h1` and `h2` once and derive
+/// `g_i = (h1 + i * h2) mod m` for `i` in `0..k`. This gives the
+/// same asymptotic false-positive behavior as `k` independent hash
+/// functions at roughly the cost of two. The final reduction into
+/// `0..m` is done by `uniform_index` rather than a plain modulo, so
+/// for a non-power-of-two `m` an index may come from a redrawn hash
+/// instead of `h1 + i * h2`. Shared by `BloomFilter` and
+/// `CountingBloomFilter` so both index into their respective arrays
+/// the same way.
+fn indices_for<T: BloomHashIndex>(value: &T, size: usize, num_hashes: usize) -> Vec<u32> {
+    let h1 = value.hash_at_index(0);
+    let h2 = value.hash_at_index(1);
+    let m = size as u32;
+
+    (0..num_hashes)
+        .map(|i| {
+            let raw = h1.wrapping_add((i as u32).wrapping_mul(h2));
+            uniform_index(value, raw, m, 2 + i as u32)
+        })
+        .collect()
+}
+
+/// Fold `raw_hash` into a uniformly distributed index in `0..m`.
+/// When `m` is a power of two, `raw_hash % m` is exactly
+/// `raw_hash & (m - 1)`, so that branch-free mask is used directly.
+/// Otherwise the naive modulo is slightly biased towards low indices
+/// (indices `0..=u32::MAX % m` are picked one extra time), so
+/// values in that biased tail are rejected and a fresh hash is
+/// drawn, deterministically reseeding `value` from `reseed_from`
+/// upward, until one lands in the unbiased range.
+fn uniform_index<T: BloomHashIndex>(value: &T, raw_hash: u32, m: u32, reseed_from: u32) -> u32 {
+    if m.is_power_of_two() {
+        return raw_hash & (m - 1);
+    }
+
+    let limit = (u32::MAX / m) * m;
+    let mut candidate = raw_hash;
+    let mut seed = reseed_from;
+    while candidate >= limit {
+        candidate = value.hash_at_index(seed);
+        seed = seed.wrapping_add(1);
+    }
+    candidate % m
+}
 
 /// The BloomFilter object. Supports two methods, `insert` and
 /// `maybe_present`.
@@ -67,15 +155,16 @@ impl BloomFilter {
 
     }
 
-    /// Insert a value into the bloom filter
+    /// Insert a value into the bloom filter. `value` can be anything
+    /// implementing `BloomHashIndex` (e.g. `&str`, `&[u8]`, or the
+    /// integer types).
     /// Params:
-    ///   value: &str - Value to insert into the bloom filter
+    ///   value: T - Value to insert into the bloom filter
     /// Returns: ()
 
-    pub fn insert(&mut self, value: &str) {
+    pub fn insert<T: BloomHashIndex>(&mut self, value: T) {
         // Generate a bit index for each of the hash functions needed
-        for i in 0..self.num_hashes {
-            let bit_index = (murmur3_32_seeded(value, i as u32) % (self.bits.size as u32)) as u32;
+        for bit_index in self.bit_indices(&value) {
             self.bits.set(bit_index as usize);
         }
     }
@@ -84,13 +173,200 @@ impl BloomFilter {
     /// because there is a chance of false positives when querying
     /// the structure.
     /// Params:
+    ///   value: T - The value to test for
+    /// Returns: true if value maybe present, false otherwise
+    pub fn maybe_present<T: BloomHashIndex>(&self, value: T) -> bool {
+        for bit_index in self.bit_indices(&value) {
+            if !self.bits.is_set(bit_index as usize) {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Derive the `k` bit indices for `value`. See `indices_for` for
+    /// the derivation (double hashing plus modulo-bias-free folding).
+    fn bit_indices<T: BloomHashIndex>(&self, value: &T) -> Vec<u32> {
+        indices_for(value, self.bits.size, self.num_hashes)
+    }
+
+    /// Reconstruct a `BloomFilter` from its raw bit array, as
+    /// previously returned by `serialize` (minus the header), along
+    /// with the `m`/`k` parameters it was created with.
+    /// Params:
+    ///   bytes: &[u8] - Raw bytes of the underlying bit vector
+    ///   size: usize - m, the number of bits in the filter
+    ///   num_hashes: usize - k, the number of hash functions used
+    /// Returns: BloomFilter
+    pub fn from_parts(bytes: &[u8], size: usize, num_hashes: usize) -> BloomFilter {
+        BloomFilter {
+            bits: BitVec::from_parts(bytes, size),
+            num_hashes: num_hashes,
+        }
+    }
+
+    /// Serialize the filter to a compact byte format: a header
+    /// holding `m` and `k` (as little-endian `u32`s) followed by the
+    /// raw bytes of the underlying bit vector.
+    /// Returns: Vec<u8>
+    pub fn serialize(&self) -> Vec<u8> {
+        let bytes = self.bits.get_bytes();
+        let mut out = Vec::with_capacity(8 + bytes.len());
+        out.extend_from_slice(&(self.bits.size as u32).to_le_bytes());
+        out.extend_from_slice(&(self.num_hashes as u32).to_le_bytes());
+        out.extend_from_slice(&bytes);
+        out
+    }
+
+    /// Deserialize a filter previously produced by `serialize`.
+    /// Params:
+    ///   bytes: &[u8] - Bytes produced by `serialize`
+    /// Returns: BloomFilter
+    pub fn deserialize(bytes: &[u8]) -> BloomFilter {
+        if bytes.len() < 8 {
+            panic!("Truncated bloom filter: expected at least an 8-byte header, got {} bytes.",
+                   bytes.len());
+        }
+
+        let m = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        let k = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+
+        let payload = &bytes[8..];
+        let expected_bytes = m.div_ceil(64) * 8;
+        if payload.len() < expected_bytes {
+            panic!("Truncated bloom filter: header declares {} bits ({} bytes), but only {} \
+                     payload bytes are present.",
+                   m,
+                   expected_bytes,
+                   payload.len());
+        }
+
+        BloomFilter::from_parts(payload, m, k)
+    }
+
+    /// Check that `self` and `other` share the same `m`/`k`
+    /// parameters, as required before combining them.
+    fn assert_compatible(&self, other: &BloomFilter) {
+        if self.bits.size != other.bits.size || self.num_hashes != other.num_hashes {
+            panic!("Cannot combine bloom filters with different m/k parameters.");
+        }
+    }
+
+    /// Return a new filter that is the union of `self` and `other`:
+    /// a value maybe-present in either input filter is maybe-present
+    /// in the result. Both filters must share the same `m` and `k`.
+    pub fn union(&self, other: &BloomFilter) -> BloomFilter {
+        self.assert_compatible(other);
+        BloomFilter {
+            bits: self.bits.union(&other.bits),
+            num_hashes: self.num_hashes,
+        }
+    }
+
+    /// Return a new filter that approximates the intersection of
+    /// `self` and `other`: a value is maybe-present in the result
+    /// only if it's maybe-present in both input filters. Both
+    /// filters must share the same `m` and `k`.
+    pub fn intersect(&self, other: &BloomFilter) -> BloomFilter {
+        self.assert_compatible(other);
+        BloomFilter {
+            bits: self.bits.intersect(&other.bits),
+            num_hashes: self.num_hashes,
+        }
+    }
+
+    /// Estimate the number of distinct items inserted so far, using
+    /// the standard cardinality estimator for bloom filters:
+    /// `n ≈ -(m/k) * ln(1 - X/m)`, where `X` is the number of bits
+    /// currently set. This also doubles as a fill-ratio signal: as
+    /// the estimate climbs past the `expected_inserts` the filter was
+    /// sized for, its real false positive rate is drifting past the
+    /// requested `fpr`.
+    /// Returns: f64
+    pub fn estimated_len(&self) -> f64 {
+        let m = self.bits.size as f64;
+        let k = self.num_hashes as f64;
+        let x = self.bits.num_bits_set() as f64;
+
+        -(m / k) * (1.0 - (x / m)).ln()
+    }
+
+}
+
+
+/// A bloom filter variant backed by small counters instead of single
+/// bits, which allows items to be removed again. Each of the `k`
+/// hash functions indexes into an 8-bit counter instead of a bit;
+/// `insert` increments the indexed counters and `remove` decrements
+/// them, so a value is only reported present while all of its
+/// counters are still above zero.
+#[allow(dead_code)]
+pub struct CountingBloomFilter {
+    counters: Vec<u8>, // Per-slot counters
+    num_hashes: usize, // # of hashes needed
+}
+
+#[allow(dead_code)]
+impl CountingBloomFilter {
+
+    /// Static constructor method. Uses the same sizing math as
+    /// `BloomFilter::new` to pick the number of counters (m) and
+    /// the number of hash functions (k) for the desired false
+    /// positive rate.
+    /// Params:
+    ///   expected_inserts: usize - Expected number of items that
+    ///                             will be inserted into the filter
+    ///   fpr: f64 - Desired false positive rate
+    /// Returns: CountingBloomFilter
+    pub fn new(expected_inserts: usize, fpr: f64) -> CountingBloomFilter {
+        if fpr <= 0.0 {
+            panic!("False positive rate must be > 0.0!");
+        }
+
+        let m: usize = ((-1.0 * (expected_inserts as f64) * fpr.ln()) / 2.0f64.ln().powf(2.0))
+                           .ceil() as usize;
+
+        let k: usize = (((m as f64) / (expected_inserts as f64)) * 2.0f64.ln()).ceil() as usize;
+
+        CountingBloomFilter {
+            counters: vec![0u8; m],
+            num_hashes: k,
+        }
+    }
+
+    /// Insert a value into the filter, incrementing each of its `k`
+    /// counters. Counters saturate at 255 rather than wrapping
+    /// around to 0.
+    /// Params:
+    ///   value: &str - Value to insert into the filter
+    /// Returns: ()
+    pub fn insert(&mut self, value: &str) {
+        for index in indices_for(&value, self.counters.len(), self.num_hashes) {
+            let index = index as usize;
+            self.counters[index] = self.counters[index].saturating_add(1);
+        }
+    }
+
+    /// Remove a value from the filter, decrementing each of its `k`
+    /// counters. Counters saturate at 0 rather than wrapping around.
+    /// Params:
+    ///   value: &str - Value to remove from the filter
+    /// Returns: ()
+    pub fn remove(&mut self, value: &str) {
+        for index in indices_for(&value, self.counters.len(), self.num_hashes) {
+            let index = index as usize;
+            self.counters[index] = self.counters[index].saturating_sub(1);
+        }
+    }
+
+    /// Test to see if a value is maybe present. True only when all
+    /// `k` of its counters are nonzero.
+    /// Params:
     ///   value: &str - The value to test for
     /// Returns: true if value maybe present, false otherwise
     pub fn maybe_present(&self, value: &str) -> bool {
-        for i in 0..self.num_hashes {
-            let bit_index = (murmur3_32_seeded(value, i as u32) % (self.bits.size as u32)) as u32;
-
-            if !self.bits.is_set(bit_index as usize) {
+        for index in indices_for(&value, self.counters.len(), self.num_hashes) {
+            if self.counters[index as usize] == 0 {
                 return false;
             }
         }
@@ -106,9 +382,9 @@ fn test_insert_and_check() {
     // Create new
     let mut bf = BloomFilter::new(2, 0.001);
     // Insert "test"
-    bf.insert(&"test");
+    bf.insert("test");
     // Assert its there
-    assert!(bf.maybe_present(&"test"));
+    assert!(bf.maybe_present("test"));
 }
 
 #[test]
@@ -116,11 +392,11 @@ fn test_check_only() {
     // Create new
     let mut bf = BloomFilter::new(2, 0.001);
     // BF is empty, all maybe_present should be false
-    assert!(bf.maybe_present(&"not") == false);
-    assert!(bf.maybe_present(&"foo") == false);
-    assert!(bf.maybe_present(&"abcdefghijklmnop") == false);
+    assert!(bf.maybe_present("not") == false);
+    assert!(bf.maybe_present("foo") == false);
+    assert!(bf.maybe_present("abcdefghijklmnop") == false);
     bf.insert("abc");
-    assert!(bf.maybe_present(&"abc"));
+    assert!(bf.maybe_present("abc"));
 }
 
 #[test]
@@ -128,3 +404,147 @@ fn test_check_only() {
 fn test_fpr_leq_0() {
     let bf = BloomFilter::new(2, 0.0);
 }
+
+#[test]
+fn test_bit_indices_deterministic_and_spread() {
+    let bf = BloomFilter::new(100, 0.01);
+    let first = bf.bit_indices(&"double-hashing");
+    let second = bf.bit_indices(&"double-hashing");
+    // Same value always derives the same indices
+    assert!(first == second);
+    // The derived indices aren't all identical, i.e. they actually
+    // spread across the bit array rather than collapsing to one slot
+    assert!(first.iter().any(|&index| index != first[0]));
+}
+
+#[test]
+fn test_serialize_deserialize_round_trip() {
+    let mut bf = BloomFilter::new(2, 0.001);
+    bf.insert("test");
+    let bytes = bf.serialize();
+    let restored = BloomFilter::deserialize(&bytes);
+    assert!(restored.maybe_present("test"));
+    assert!(restored.maybe_present("not") == false);
+}
+
+#[test]
+#[should_panic]
+fn test_deserialize_truncated() {
+    BloomFilter::deserialize(&[1, 2, 3]);
+}
+
+#[test]
+#[should_panic]
+fn test_deserialize_truncated_payload() {
+    // Valid 8-byte header claiming m=1000 bits, but no payload at all.
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&1000u32.to_le_bytes());
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    BloomFilter::deserialize(&bytes);
+}
+
+#[test]
+fn test_bit_index_uniform_for_non_power_of_two_size() {
+    // m = 1000 is not a power of two, so naive `% m` would skew
+    // towards low indices.
+    let m = 1000;
+    let bf = BloomFilter::from_parts(&vec![0u8; m], m, 1);
+
+    let bucket_count = 10;
+    let bucket_size = m / bucket_count;
+    let mut buckets = vec![0usize; bucket_count];
+
+    let samples = 4000;
+    for i in 0..samples {
+        let key = format!("key-{}", i);
+        let index = bf.bit_indices(&key.as_str())[0] as usize;
+        buckets[(index / bucket_size).min(bucket_count - 1)] += 1;
+    }
+
+    let expected = samples / bucket_count;
+    for count in buckets {
+        let deviation = (count as f64 - expected as f64).abs() / expected as f64;
+        assert!(deviation < 0.35,
+                "bucket count {} deviates too far from expected {}",
+                count,
+                expected);
+    }
+}
+
+#[test]
+fn test_insert_integer() {
+    let mut bf = BloomFilter::new(2, 0.001);
+    bf.insert(42u32);
+    assert!(bf.maybe_present(42u32));
+    assert!(bf.maybe_present(7u32) == false);
+}
+
+#[test]
+fn test_insert_bytes() {
+    let mut bf = BloomFilter::new(2, 0.001);
+    let value: &[u8] = &[1, 2, 3, 4];
+    bf.insert(value);
+    assert!(bf.maybe_present(value));
+}
+
+#[test]
+fn test_union() {
+    let mut a = BloomFilter::new(2, 0.001);
+    let mut b = BloomFilter::new(2, 0.001);
+    a.insert("a-only");
+    b.insert("b-only");
+    let unioned = a.union(&b);
+    assert!(unioned.maybe_present("a-only"));
+    assert!(unioned.maybe_present("b-only"));
+}
+
+#[test]
+fn test_intersect() {
+    let mut a = BloomFilter::new(2, 0.001);
+    let mut b = BloomFilter::new(2, 0.001);
+    a.insert("shared");
+    b.insert("shared");
+    let intersected = a.intersect(&b);
+    assert!(intersected.maybe_present("shared"));
+}
+
+#[test]
+#[should_panic]
+fn test_union_size_mismatch() {
+    let a = BloomFilter::new(2, 0.001);
+    let b = BloomFilter::new(200, 0.001);
+    a.union(&b);
+}
+
+#[test]
+fn test_estimated_len() {
+    let mut bf = BloomFilter::new(1000, 0.01);
+    assert!(bf.estimated_len() == 0.0);
+
+    for i in 0..100 {
+        bf.insert(format!("item-{}", i).as_str());
+    }
+
+    let estimate = bf.estimated_len();
+    // The estimator is approximate; just check it's in a sane range
+    // around the true count of 100 distinct inserts.
+    assert!(estimate > 80.0 && estimate < 120.0,
+            "estimate {} not close to 100",
+            estimate);
+}
+
+#[test]
+fn test_counting_insert_and_check() {
+    let mut cbf = CountingBloomFilter::new(2, 0.001);
+    cbf.insert(&"test");
+    assert!(cbf.maybe_present(&"test"));
+}
+
+#[test]
+fn test_counting_remove() {
+    let mut cbf = CountingBloomFilter::new(2, 0.001);
+    cbf.insert("abc");
+    assert!(cbf.maybe_present(&"abc"));
+    cbf.remove("abc");
+    assert!(cbf.maybe_present(&"abc") == false);
+}
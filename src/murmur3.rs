@@ -0,0 +1,83 @@
+//! MurmurHash3 (x86, 32-bit) implementation.
+//! By: Brian A. Madden - brian.a.madden@gmail.com
+
+/// Compute the 32-bit MurmurHash3 of `key` using `seed` as the
+/// initial hash value. Varying `seed` gives independent-looking
+/// hashes of the same key; `BloomFilter` uses seeds 0 and 1 as the
+/// two base hashes from which it derives its `k` bit indices.
+pub fn murmur3_32_seeded(key: &str, seed: u32) -> u32 {
+    murmur3_32_seeded_bytes(key.as_bytes(), seed)
+}
+
+/// Compute the 32-bit MurmurHash3 of a raw byte slice, using `seed`
+/// as the initial hash value. `murmur3_32_seeded` is just this
+/// applied to a string's UTF-8 bytes.
+pub fn murmur3_32_seeded_bytes(key_bytes: &[u8], seed: u32) -> u32 {
+    let c1: u32 = 0xcc9e2d51;
+    let c2: u32 = 0x1b873593;
+    let r1: u32 = 15;
+    let r2: u32 = 13;
+    let m: u32 = 5;
+    let n: u32 = 0xe6546b64;
+
+    let mut hash = seed;
+    let len = key_bytes.len();
+
+    let mut chunks = key_bytes.chunks(4);
+    for chunk in &mut chunks {
+        if chunk.len() == 4 {
+            let mut k = key_bytes_to_u32_chunk(chunk);
+
+            k = k.wrapping_mul(c1);
+            k = k.rotate_left(r1);
+            k = k.wrapping_mul(c2);
+
+            hash ^= k;
+            hash = hash.rotate_left(r2);
+            hash = hash.wrapping_mul(m).wrapping_add(n);
+        } else {
+            let mut k = key_bytes_to_u32_chunk(chunk);
+
+            k = k.wrapping_mul(c1);
+            k = k.rotate_left(r1);
+            k = k.wrapping_mul(c2);
+
+            hash ^= k;
+        }
+    }
+
+    hash ^= len as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85ebca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2ae35);
+    hash ^= hash >> 16;
+
+    hash
+}
+
+/// MurmurHash3 with a seed of 0.
+#[allow(dead_code)]
+pub fn murmur3_32(key: &str) -> u32 {
+    murmur3_32_seeded(key, 0)
+}
+
+fn key_bytes_to_u32_chunk(bytes: &[u8]) -> u32 {
+    // TODO: Ensure that we're dealing with LE architecture,
+    // if not flip the bytes
+    match bytes.len() {
+        4 => {
+            ((bytes[3] as u32) << 24) | ((bytes[2] as u32) << 16) | ((bytes[1] as u32) << 8) |
+            (bytes[0] as u32)
+        }
+        3 => ((bytes[2] as u32) << 16) | ((bytes[1] as u32) << 8) | (bytes[0] as u32),
+        2 => ((bytes[1] as u32) << 8) | (bytes[0] as u32),
+        1 => bytes[0] as u32,
+        _ => 0,
+    }
+}
+
+#[test]
+fn murmur3_32_hello_test() {
+    assert!(murmur3_32("hello") == 613153351);
+}